@@ -0,0 +1,213 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::Range;
+use std::path::Path;
+
+use crossterm::style::Color;
+use ropey::Rope;
+use syntect::parsing::{ParseState, ScopeStack, SyntaxSet};
+
+use crate::theme;
+
+// A single character-range insertion, reversible by deleting
+// `text` back out starting at `char_idx`.
+#[derive(Clone)]
+struct Edit {
+    char_idx: usize,
+    text: String,
+}
+
+pub struct Buffer {
+    pub file: Option<String>,
+    rope: Rope,
+    syntax_set: SyntaxSet,
+    // `parse_states[i]` / `scope_stacks[i]` are snapshots taken *before*
+    // line `i` is parsed, so re-highlighting from an edited line forward
+    // only needs the snapshot at that line, not a parse from the top.
+    parse_states: Vec<ParseState>,
+    scope_stacks: Vec<ScopeStack>,
+    highlighted: Vec<Vec<(Color, Range<usize>)>>,
+    undo_stack: Vec<Vec<Edit>>,
+    redo_stack: Vec<Vec<Edit>>,
+    // Open while an insert-mode session is in progress, so consecutive
+    // single-char inserts coalesce into one undo step.
+    pending_group: Option<Vec<Edit>>,
+}
+
+impl Buffer {
+    pub fn from_file(file: Option<String>) -> Self {
+        let rope = file
+            .as_deref()
+            .and_then(|path| File::open(path).ok())
+            .and_then(|f| Rope::from_reader(BufReader::new(f)).ok())
+            .unwrap_or_default();
+
+        let syntax_set = SyntaxSet::load_defaults_newlines();
+        let syntax = file
+            .as_deref()
+            .and_then(|path| Path::new(path).extension())
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| syntax_set.find_syntax_by_extension(ext))
+            .unwrap_or_else(|| syntax_set.find_syntax_plain_text());
+
+        let parse_state = ParseState::new(syntax);
+
+        Buffer {
+            file,
+            rope,
+            syntax_set,
+            parse_states: vec![parse_state],
+            scope_stacks: vec![ScopeStack::new()],
+            highlighted: Vec::new(),
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            pending_group: None,
+        }
+    }
+
+    pub fn get(&self, line: usize) -> Option<String> {
+        if line >= self.len() {
+            return None;
+        }
+        let text = self.rope.line(line).to_string();
+        Some(text.trim_end_matches(['\n', '\r']).to_string())
+    }
+
+    // `Rope::len_lines` counts a phantom trailing empty line for any text
+    // ending in '\n' (i.e. almost every real file); drop it so line counts
+    // and navigation match what the file actually looks like on screen.
+    pub fn len(&self) -> usize {
+        let lines = self.rope.len_lines();
+        if lines > 1 && self.rope.line(lines - 1).len_chars() == 0 {
+            lines - 1
+        } else {
+            lines
+        }
+    }
+
+    pub fn insert(&mut self, cx: u16, line: u16, c: char) {
+        let char_idx = self.rope.line_to_char(line as usize) + cx as usize;
+        self.rope.insert_char(char_idx, c);
+        self.record_edit(char_idx, c.to_string());
+        self.invalidate_from(line as usize);
+    }
+
+    pub fn insert_newline(&mut self, cx: u16, line: u16) {
+        let char_idx = self.rope.line_to_char(line as usize) + cx as usize;
+        self.rope.insert_char(char_idx, '\n');
+        self.record_edit(char_idx, "\n".to_string());
+        self.invalidate_from(line as usize);
+    }
+
+    // Edits recorded before the matching `end_undo_group` coalesce into
+    // a single undo step.
+    pub fn begin_undo_group(&mut self) {
+        self.pending_group = Some(Vec::new());
+    }
+
+    pub fn end_undo_group(&mut self) {
+        if let Some(group) = self.pending_group.take() {
+            if !group.is_empty() {
+                self.undo_stack.push(group);
+            }
+        }
+    }
+
+    fn record_edit(&mut self, char_idx: usize, text: String) {
+        self.redo_stack.clear();
+        let edit = Edit { char_idx, text };
+        match &mut self.pending_group {
+            Some(group) => group.push(edit),
+            None => self.undo_stack.push(vec![edit]),
+        }
+    }
+
+    pub fn undo(&mut self) -> Option<(u16, u16)> {
+        self.end_undo_group();
+        let group = self.undo_stack.pop()?;
+
+        let mut inverse = Vec::with_capacity(group.len());
+        let mut cursor_idx = 0;
+        for edit in group.iter().rev() {
+            let len = edit.text.chars().count();
+            self.rope.remove(edit.char_idx..edit.char_idx + len);
+            cursor_idx = edit.char_idx;
+            inverse.push(edit.clone());
+        }
+        self.redo_stack.push(inverse);
+        self.invalidate_from(0);
+        Some(self.char_idx_to_cursor(cursor_idx))
+    }
+
+    pub fn redo(&mut self) -> Option<(u16, u16)> {
+        let group = self.redo_stack.pop()?;
+
+        let mut reapplied = Vec::with_capacity(group.len());
+        let mut cursor_idx = 0;
+        for edit in group.iter().rev() {
+            self.rope.insert(edit.char_idx, &edit.text);
+            cursor_idx = edit.char_idx + edit.text.chars().count();
+            reapplied.push(edit.clone());
+        }
+        self.undo_stack.push(reapplied);
+        self.invalidate_from(0);
+        Some(self.char_idx_to_cursor(cursor_idx))
+    }
+
+    fn char_idx_to_cursor(&self, char_idx: usize) -> (u16, u16) {
+        let char_idx = char_idx.min(self.rope.len_chars());
+        let line = self.rope.char_to_line(char_idx);
+        let col = char_idx - self.rope.line_to_char(line);
+        (line as u16, col as u16)
+    }
+
+    pub fn write(&self) -> std::io::Result<()> {
+        if let Some(file) = &self.file {
+            std::fs::write(file, self.rope.to_string())?;
+        }
+        Ok(())
+    }
+
+    pub fn highlighted_line(&mut self, line: usize) -> &[(Color, Range<usize>)] {
+        while self.highlighted.len() <= line && self.highlighted.len() < self.len() {
+            self.parse_next_line();
+        }
+        self.highlighted.get(line).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    fn parse_next_line(&mut self) {
+        let idx = self.highlighted.len();
+        let mut state = self.parse_states[idx].clone();
+        let mut stack = self.scope_stacks[idx].clone();
+
+        let text = format!("{}\n", self.get(idx).unwrap_or_default());
+        let ops = state
+            .parse_line(&text, &self.syntax_set)
+            .unwrap_or_default();
+
+        let mut spans = Vec::new();
+        let mut start = 0;
+        for (pos, op) in ops {
+            if pos > start {
+                spans.push((theme::color_for_scope(&stack), start..pos));
+            }
+            let _ = stack.apply(&op);
+            start = pos;
+        }
+        if start < text.len() {
+            spans.push((theme::color_for_scope(&stack), start..text.len()));
+        }
+
+        self.highlighted.push(spans);
+        self.parse_states.push(state);
+        self.scope_stacks.push(stack);
+    }
+
+    // Drops cached snapshots from `line` onward so the next read re-parses
+    // instead of serving stale highlighting.
+    fn invalidate_from(&mut self, line: usize) {
+        self.parse_states.truncate(line + 1);
+        self.scope_stacks.truncate(line + 1);
+        self.highlighted.truncate(line);
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::HashMap;
+use std::fs;
+
+use crossterm::event::{KeyCode, KeyModifiers};
+
+use crate::editor::Editor;
+
+// Missing or unparsable files just fall back to the built-ins.
+pub const CONFIG_FILE: &str = "red.toml";
+
+pub type ActionFn = fn(&mut Editor);
+
+// A named-action registry plus the key chord -> action name mapping
+// used to dispatch `handle_normal_event`. Only normal mode is mapped;
+// insert- and command-mode keys are matched in `editor.rs` directly.
+pub struct Keymap {
+    registry: HashMap<&'static str, ActionFn>,
+    normal: HashMap<String, String>,
+}
+
+impl Keymap {
+    pub fn load(path: &str) -> Self {
+        let mut normal = default_bindings();
+        if let Ok(contents) = fs::read_to_string(path) {
+            if let Ok(value) = contents.parse::<toml::Value>() {
+                if let Some(table) = value.get("normal").and_then(|v| v.as_table()) {
+                    for (chord, action) in table {
+                        if let Some(action) = action.as_str() {
+                            normal.insert(chord.clone(), action.to_string());
+                        }
+                    }
+                }
+            }
+        }
+
+        Keymap {
+            registry: default_registry(),
+            normal,
+        }
+    }
+
+    pub fn resolve(&self, code: KeyCode, modifiers: KeyModifiers) -> Option<ActionFn> {
+        let chord = chord_name(code, modifiers)?;
+        let action_name = self.normal.get(&chord)?;
+        self.registry.get(action_name.as_str()).copied()
+    }
+}
+
+fn chord_name(code: KeyCode, modifiers: KeyModifiers) -> Option<String> {
+    let key = match code {
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        _ => return None,
+    };
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        Some(format!("ctrl-{key}"))
+    } else {
+        Some(key)
+    }
+}
+
+fn default_bindings() -> HashMap<String, String> {
+    [
+        ("q", "quit"),
+        ("$", "goto_line_end"),
+        ("0", "goto_line_start"),
+        ("h", "move_char_left"),
+        ("left", "move_char_left"),
+        ("l", "move_char_right"),
+        ("right", "move_char_right"),
+        ("k", "move_line_up"),
+        ("up", "move_line_up"),
+        ("j", "move_line_down"),
+        ("down", "move_line_down"),
+        ("i", "insert_mode"),
+        (":", "command_mode"),
+        ("w", "move_next_word_start"),
+        ("e", "move_next_word_end"),
+        ("b", "move_prev_word_start"),
+        ("u", "undo"),
+        ("ctrl-f", "page_down"),
+        ("ctrl-b", "page_up"),
+        ("ctrl-r", "redo"),
+    ]
+    .into_iter()
+    .map(|(chord, action)| (chord.to_string(), action.to_string()))
+    .collect()
+}
+
+fn default_registry() -> HashMap<&'static str, ActionFn> {
+    let mut registry: HashMap<&'static str, ActionFn> = HashMap::new();
+    registry.insert("quit", Editor::request_quit);
+    registry.insert("goto_line_end", Editor::goto_line_end);
+    registry.insert("goto_line_start", Editor::goto_line_start);
+    registry.insert("move_char_left", Editor::move_char_left);
+    registry.insert("move_char_right", Editor::move_char_right);
+    registry.insert("move_line_up", Editor::move_line_up);
+    registry.insert("move_line_down", Editor::move_line_down);
+    registry.insert("insert_mode", Editor::enter_insert_mode);
+    registry.insert("command_mode", Editor::enter_command_mode);
+    registry.insert("move_next_word_start", Editor::move_next_word_start);
+    registry.insert("move_next_word_end", Editor::move_next_word_end);
+    registry.insert("move_prev_word_start", Editor::move_prev_word_start);
+    registry.insert("undo", Editor::undo);
+    registry.insert("redo", Editor::redo);
+    registry.insert("page_up", Editor::page_up);
+    registry.insert("page_down", Editor::page_down);
+    registry
+}
@@ -3,28 +3,46 @@ use crossterm::{terminal, ExecutableCommand, QueueableCommand, cursor, event, st
 use crossterm::event::{read};
 use crossterm::style::{Color, Stylize};
 use crate::buffer::Buffer;
+use crate::config::{self, Keymap};
 
 enum Action {
     Quit,
-    MoveUp,
-    MoveDown,
-    MoveLeft,
-    MoveRight,
-    PageDown,
 
     AddChar(char),
     NewLine,
 
     EnterMode(Mode),
-    PageUp,
-    MoveToLineEnd,
-    MoveToLineStart,
+
+    AppendToCommand(char),
+    CommandBackspace,
+    Write,
+    WriteAndQuit,
+    GotoLine(u16),
+    SetRelativeNumbers(bool),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum CharClass {
+    Whitespace,
+    Word,
+    Punct,
+}
+
+fn classify(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Whitespace
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
 }
 
 #[derive(Debug)]
 enum Mode {
     Normal,
-    Insert
+    Insert,
+    Command,
 }
 
 pub struct Editor {
@@ -35,7 +53,11 @@ pub struct Editor {
     vleft: u16,
     cx: u16,
     cy: u16,
-    mode: Mode
+    mode: Mode,
+    command: String,
+    keymap: Keymap,
+    quit: bool,
+    relative_numbers: bool,
 }
 
 impl Drop for Editor {
@@ -62,7 +84,11 @@ impl Editor {
             vleft: 0,
             cx: 0,
             cy: 0,
-            mode: Mode::Normal
+            mode: Mode::Normal,
+            command: String::new(),
+            keymap: Keymap::load(config::CONFIG_FILE),
+            quit: false,
+            relative_numbers: false,
         })
     }
 
@@ -71,7 +97,17 @@ impl Editor {
     }
 
     fn vheight(&self) -> u16 {
-        self.size.1 - 2
+        self.size.1.saturating_sub(2)
+    }
+
+    // Enough digits for the last line number plus one padding column.
+    fn gutter_width(&self) -> u16 {
+        let digits = (self.buffer.len().max(1) as u32).ilog10() + 1;
+        digits as u16 + 1
+    }
+
+    fn text_width(&self) -> u16 {
+        self.vwidth().saturating_sub(self.gutter_width())
     }
 
     fn line_length(&self) -> u16 {
@@ -93,34 +129,100 @@ impl Editor {
     pub fn draw(&mut self) -> anyhow::Result<()> {
         self.draw_viewport()?;
         self.draw_statusline()?;
-        self.stdout.queue(cursor::MoveTo(self.cx, self.cy))?;
+        self.draw_commandline()?;
+        match self.mode {
+            Mode::Command => {
+                self.stdout
+                    .queue(cursor::MoveTo(1 + self.command.len() as u16, self.size.1.saturating_sub(1)))?;
+            }
+            _ => {
+                self.stdout
+                    .queue(cursor::MoveTo(self.cx + self.gutter_width(), self.cy))?;
+            }
+        }
         self.stdout.flush()?;
         Ok(())
     }
 
-    pub fn draw_viewport(&mut self) -> anyhow::Result<()> {
+    pub fn draw_commandline(&mut self) -> anyhow::Result<()> {
         let vwidth = self.vwidth() as usize;
+        let text = match self.mode {
+            Mode::Command => format!(":{}", self.command),
+            _ => String::new(),
+        };
+        self.stdout
+            .queue(cursor::MoveTo(0, self.size.1.saturating_sub(1)))?
+            .queue(style::Print(format!("{text:<width$}", width = vwidth)))?;
+        Ok(())
+    }
+
+    pub fn draw_viewport(&mut self) -> anyhow::Result<()> {
+        let gutter_width = self.gutter_width();
+        let vwidth = self.text_width() as usize;
+        let current_line = self.buffer_line();
+
         for i in 0..self.vheight() {
-            let line = self.viewport_line(i).unwrap_or_else(|| String::new());
-            self.stdout
-                .queue(cursor::MoveTo(0, i))?
-                .queue(style::Print(format!("{line:<width$}", width=vwidth)))?;
+            let buffer_line = (self.vtop + i) as usize;
+            self.stdout.queue(cursor::MoveTo(0, i))?;
+            self.stdout.queue(style::Print(
+                self.gutter_text(buffer_line, current_line, gutter_width),
+            ))?;
+
+            let line = self.buffer.get(buffer_line).unwrap_or_else(|| String::new());
+            let spans = self.buffer.highlighted_line(buffer_line).to_vec();
+
+            let mut printed = 0;
+            for (color, range) in spans {
+                let end = range.end.min(line.len());
+                if range.start >= end {
+                    continue;
+                }
+                self.stdout
+                    .queue(style::PrintStyledContent(line[range.start..end].to_string().with(color)))?;
+                printed += end - range.start;
+            }
+            if printed < vwidth {
+                self.stdout
+                    .queue(style::Print(" ".repeat(vwidth - printed)))?;
+            }
         }
         Ok(())
     }
 
+    // Absolute line number, or (in relative mode) the distance from
+    // `current_line` — which still shows its absolute number, as in vim's
+    // `relativenumber`.
+    fn gutter_text(&self, buffer_line: usize, current_line: u16, gutter_width: u16) -> String {
+        if buffer_line >= self.buffer.len() {
+            return " ".repeat(gutter_width as usize);
+        }
+
+        let number = if self.relative_numbers && buffer_line as u16 != current_line {
+            (buffer_line as i64 - current_line as i64).unsigned_abs()
+        } else {
+            buffer_line as u64 + 1
+        };
+
+        format!("{number:>width$} ", width = (gutter_width - 1) as usize)
+    }
+
     pub fn draw_statusline(&mut self) -> anyhow::Result<()> {
         let mode = format!(" {:?} ", self.mode).to_uppercase();
         let file = format!(" {}", self.buffer.file.as_deref().unwrap_or("untitled"));
         let pos = format!(" {}:{} ", self.cx, self.cy);
 
-        let file_width = self.size.0 - mode.len() as u16 - pos.len() as u16 - 2;
+        let file_width = self
+            .size
+            .0
+            .saturating_sub(mode.len() as u16)
+            .saturating_sub(pos.len() as u16)
+            .saturating_sub(2);
 
         let normal_bg = Color::Rgb {r:184, g:144, b:243};
         //let insert_bg = 1;
         let classic_bg = Color::Rgb {r:67, g:70, b:89};
 
-        self.stdout.queue(cursor::MoveTo(0, self.size.1 - 2))?;
+        self.stdout.queue(cursor::MoveTo(0, self.size.1.saturating_sub(2)))?;
         self.stdout.queue(style::PrintStyledContent(
             mode.with(Color::Black).bold().on(normal_bg)
         ))?;
@@ -140,6 +242,215 @@ impl Editor {
         Ok(())
     }
 
+    fn set_mode(&mut self, new: Mode) {
+        if matches!(new, Mode::Command) || matches!(self.mode, Mode::Command) {
+            self.command.clear();
+        }
+        if matches!(new, Mode::Insert) {
+            self.buffer.begin_undo_group();
+        } else if matches!(self.mode, Mode::Insert) {
+            self.buffer.end_undo_group();
+        }
+        self.mode = new;
+    }
+
+    pub(crate) fn request_quit(&mut self) {
+        self.quit = true;
+    }
+
+    pub(crate) fn move_line_up(&mut self) {
+        if self.cy == 0 {
+            // Scroll up
+            if self.vtop > 0 {
+                self.vtop -= 1;
+            }
+        } else {
+            self.cy = self.cy.saturating_sub(1);
+        }
+    }
+
+    pub(crate) fn move_line_down(&mut self) {
+        self.cy += 1;
+        if self.cy >= self.vheight() {
+            // Scroll down
+            self.vtop += 1;
+            self.cy = self.vheight() - 1;
+        }
+    }
+
+    pub(crate) fn move_char_left(&mut self) {
+        self.cx = self.cx.saturating_sub(1);
+        if self.cx < self.vleft {
+            self.cx = self.vleft;
+        }
+    }
+
+    pub(crate) fn move_char_right(&mut self) {
+        self.cx += 1;
+    }
+
+    pub(crate) fn page_up(&mut self) {
+        self.vtop = self.vtop.saturating_sub(self.vheight());
+    }
+
+    pub(crate) fn page_down(&mut self) {
+        if self.buffer.len() > (self.vtop + self.vheight()) as usize {
+            self.vtop += self.vheight();
+        } else {
+            self.vtop = self.buffer.len() as u16 - 1;
+        }
+    }
+
+    pub(crate) fn goto_line_end(&mut self) {
+        self.cx = self.line_length().saturating_sub(1);
+    }
+
+    pub(crate) fn goto_line_start(&mut self) {
+        self.cx = 0;
+    }
+
+    pub(crate) fn enter_insert_mode(&mut self) {
+        self.set_mode(Mode::Insert);
+    }
+
+    pub(crate) fn enter_command_mode(&mut self) {
+        self.set_mode(Mode::Command);
+    }
+
+    pub(crate) fn undo(&mut self) {
+        if let Some((line, col)) = self.buffer.undo() {
+            self.set_cursor(line, col);
+        }
+    }
+
+    pub(crate) fn redo(&mut self) {
+        if let Some((line, col)) = self.buffer.redo() {
+            self.set_cursor(line, col);
+        }
+    }
+
+    // Moves the cursor to (line, col) in buffer coordinates, scrolling the
+    // viewport so the target line stays visible.
+    fn set_cursor(&mut self, line: u16, col: u16) {
+        if line < self.vtop {
+            self.vtop = line;
+        } else if line >= self.vtop + self.vheight() {
+            self.vtop = line + 1 - self.vheight();
+        }
+        self.cy = line - self.vtop;
+        self.cx = col;
+    }
+
+    pub(crate) fn move_next_word_start(&mut self) {
+        let mut line = self.buffer_line();
+        let mut col = self.cx as usize;
+
+        if let Some(text) = self.buffer.get(line as usize) {
+            let chars: Vec<char> = text.chars().collect();
+            if let Some(&c) = chars.get(col) {
+                let start_class = classify(c);
+                while chars.get(col).map(|&c| classify(c)) == Some(start_class) {
+                    col += 1;
+                }
+            }
+        }
+
+        while let Some(text) = self.buffer.get(line as usize) {
+            let chars: Vec<char> = text.chars().collect();
+            while chars.get(col).map(|&c| classify(c)) == Some(CharClass::Whitespace) {
+                col += 1;
+            }
+            if col < chars.len() {
+                break;
+            }
+            if line as usize + 1 >= self.buffer.len() {
+                col = chars.len();
+                break;
+            }
+            line += 1;
+            col = 0;
+            if self.buffer.get(line as usize).is_some_and(|l| l.is_empty()) {
+                break;
+            }
+        }
+
+        self.set_cursor(line, col as u16);
+    }
+
+    pub(crate) fn move_prev_word_start(&mut self) {
+        let mut line = self.buffer_line();
+        let mut col = self.cx as usize;
+
+        loop {
+            if col == 0 {
+                if line == 0 {
+                    self.set_cursor(0, 0);
+                    return;
+                }
+                line -= 1;
+                col = self.buffer.get(line as usize).map(|l| l.chars().count()).unwrap_or(0);
+                if col == 0 {
+                    break;
+                }
+                continue;
+            }
+            col -= 1;
+            let Some(text) = self.buffer.get(line as usize) else { break };
+            if text.chars().nth(col).is_some_and(|c| classify(c) != CharClass::Whitespace) {
+                break;
+            }
+        }
+
+        if let Some(text) = self.buffer.get(line as usize) {
+            let chars: Vec<char> = text.chars().collect();
+            while col > 0 && chars.get(col - 1).map(|&c| classify(c)) == chars.get(col).map(|&c| classify(c)) {
+                col -= 1;
+            }
+        }
+
+        self.set_cursor(line, col as u16);
+    }
+
+    pub(crate) fn move_next_word_end(&mut self) {
+        let mut line = self.buffer_line();
+        let mut col = self.cx as usize + 1;
+
+        loop {
+            let Some(text) = self.buffer.get(line as usize) else { return };
+            let chars: Vec<char> = text.chars().collect();
+
+            while chars.get(col).map(|&c| classify(c)) == Some(CharClass::Whitespace) {
+                col += 1;
+            }
+
+            if col < chars.len() {
+                let class = classify(chars[col]);
+                while chars.get(col + 1).map(|&c| classify(c)) == Some(class) {
+                    col += 1;
+                }
+                break;
+            }
+
+            if line as usize + 1 >= self.buffer.len() {
+                col = chars.len().saturating_sub(1);
+                break;
+            }
+            line += 1;
+            col = 0;
+        }
+
+        self.set_cursor(line, col as u16);
+    }
+
+    fn goto_line(&mut self, line: u16) {
+        let last = self.buffer.len().saturating_sub(1) as u16;
+        let target = line.saturating_sub(1).min(last);
+        if target < self.vtop || target >= self.vtop + self.vheight() {
+            self.vtop = target.saturating_sub(self.vheight() / 2);
+        }
+        self.cy = target - self.vtop;
+    }
+
     fn check_bounds(&mut self) {
         let l = self.line_length();
         if self.cx >= l {
@@ -149,8 +460,8 @@ impl Editor {
                 self.cx = 0;
             }
         }
-        if self.cx >= self.vwidth() {
-            self.cx = self.vwidth() - 1;
+        if self.cx >= self.text_width() {
+            self.cx = self.text_width().saturating_sub(1);
         }
 
         let line_on_buffer = self.cy + self.vtop;
@@ -165,103 +476,76 @@ impl Editor {
             self.draw()?;
             if let Some(action) = self.handle_event(read()?)? {
                 match action {
-                    Action::Quit => break,
-                    Action::MoveUp => {
-                        if self.cy == 0 {
-                            // Scroll up
-                            if self.vtop > 0 {
-                                self.vtop -= 1;
-                            }
-                        } else {
-                            self.cy = self.cy.saturating_sub(1)
-                        }
-                    },
-                    Action::MoveDown => {
-                        self.cy += 1;
-                        if self.cy >= self.vheight() {
-                            // Scroll down
-                            self.vtop += 1;
-                            self.cy = self.vheight() - 1;
-                        }
-                    },
-                    Action::MoveLeft => {
-                        self.cx = self.cx.saturating_sub(1);
-                        if self.cx < self.vleft {
-                            self.cx = self.vleft;
-                        }
-                    },
-                    Action::MoveRight => {
-                        self.cx += 1;
-                    },
-                    Action::PageUp => {
-                        self.vtop = self.vtop.saturating_sub(self.vheight())
-                    }
-                    Action::PageDown => {
-                        if self.buffer.len() > (self.vtop + self.vheight()) as usize {
-                            self.vtop += self.vheight();
-                        } else {
-                            self.vtop = self.buffer.len() as u16 - 1;
-                        }
-                    }
-                    Action::MoveToLineEnd => {
-                        self.cx = self.line_length().saturating_sub(1);
-                    }
-                    Action::MoveToLineStart => {
-                        self.cx = 0;
-                    }
-                    Action::EnterMode(new) => self.mode = new,
+                    Action::Quit => self.quit = true,
+                    Action::EnterMode(new) => self.set_mode(new),
                     Action::AddChar(c) => {
                         self.buffer.insert(self.cx, self.buffer_line(), c);
                         self.cx += 1;
                     }
                     Action::NewLine => {
+                        self.buffer.insert_newline(self.cx, self.buffer_line());
                         self.cx = 0;
-                        self.cy += 1;
+                        self.move_line_down();
+                    }
+                    Action::AppendToCommand(c) => self.command.push(c),
+                    Action::CommandBackspace => {
+                        self.command.pop();
+                    }
+                    Action::Write => {
+                        self.buffer.write()?;
+                        self.mode = Mode::Normal;
+                    }
+                    Action::WriteAndQuit => {
+                        self.buffer.write()?;
+                        self.quit = true;
+                    }
+                    Action::GotoLine(n) => {
+                        self.goto_line(n);
+                        self.mode = Mode::Normal;
+                    }
+                    Action::SetRelativeNumbers(relative) => {
+                        self.relative_numbers = relative;
+                        self.mode = Mode::Normal;
                     }
                 }
             }
+            if self.quit {
+                break;
+            }
         }
         Ok(())
     }
 
 
     fn handle_event(&mut self, ev: event::Event) -> anyhow::Result<Option<Action>>{
-        // if matches!(ev, event::Event::Resize(_, _)) {
-        //     self.size = terminal::size()?;
-        // }
+        if let event::Event::Resize(w, h) = ev {
+            self.size = (w, h);
+            self.check_bounds();
+            return Ok(None);
+        }
         match self.mode {
             Mode::Normal => self.handle_normal_event(ev),
             Mode::Insert => self.handle_insert_event(ev),
+            Mode::Command => self.handle_command_event(ev),
         }
     }
 
-    fn handle_normal_event(&self, ev: event::Event) -> anyhow::Result<Option<Action>> {
-    match ev {
-        event::Event::Key(event::KeyEvent {
-                              code,
-                              kind: event::KeyEventKind::Press,
-                              modifiers,
-                              ..
-                          }) => match code {
-            event::KeyCode::Char('q') => Ok(Some(Action::Quit)),
-            event::KeyCode::Char('$') => Ok(Some(Action::MoveToLineEnd)),
-            event::KeyCode::Char('0') => Ok(Some(Action::MoveToLineStart)),
-            event::KeyCode::Char('h') | event::KeyCode::Left  => Ok(Some(Action::MoveLeft)),
-            event::KeyCode::Char('l') | event::KeyCode::Right => Ok(Some(Action::MoveRight)),
-            event::KeyCode::Char('k') | event::KeyCode::Up    => Ok(Some(Action::MoveUp)),
-            event::KeyCode::Char('j') | event::KeyCode::Down  => Ok(Some(Action::MoveDown)),
-            event::KeyCode::Char('i')              => Ok(Some(Action::EnterMode(Mode::Insert))),
-            event::KeyCode::Char('f') if matches!(modifiers, event::KeyModifiers::CONTROL) => {
-                Ok(Some(Action::PageDown))
-            },
-            event::KeyCode::Char('b') if matches!(modifiers, event::KeyModifiers::CONTROL) => {
-                Ok(Some(Action::PageUp))
-            },
-            _ => Ok(None),
-        },
-        _ => Ok(None),
+    // Dispatches the bound action function directly instead of routing
+    // through Action — normal-mode bindings are data-driven via self.keymap.
+    fn handle_normal_event(&mut self, ev: event::Event) -> anyhow::Result<Option<Action>> {
+        if let event::Event::Key(event::KeyEvent {
+            code,
+            kind: event::KeyEventKind::Press,
+            modifiers,
+            ..
+        }) = ev
+        {
+            if let Some(action_fn) = self.keymap.resolve(code, modifiers) {
+                action_fn(self);
+            }
+        }
+        Ok(None)
     }
-}
 
     fn handle_insert_event(&self, ev: event::Event) -> anyhow::Result<Option<Action>> {
         match ev {
@@ -270,10 +554,41 @@ impl Editor {
                                   kind: event::KeyEventKind::Press,
                                   .. }) => match code {
                 event::KeyCode::Esc => Ok(Some(Action::EnterMode(Mode::Normal))),
+                event::KeyCode::Enter => Ok(Some(Action::NewLine)),
                 event::KeyCode::Char(c) =>  Ok(Some(Action::AddChar(c))),
                 _ => Ok(None)
             }
             _ => Ok(None)
         }
     }
+
+    fn handle_command_event(&self, ev: event::Event) -> anyhow::Result<Option<Action>> {
+        match ev {
+            event::Event::Key(event::KeyEvent {
+                                  code,
+                                  kind: event::KeyEventKind::Press,
+                                  .. }) => match code {
+                event::KeyCode::Esc => Ok(Some(Action::EnterMode(Mode::Normal))),
+                event::KeyCode::Backspace => Ok(Some(Action::CommandBackspace)),
+                event::KeyCode::Enter => Ok(Some(self.parse_command())),
+                event::KeyCode::Char(c) => Ok(Some(Action::AppendToCommand(c))),
+                _ => Ok(None)
+            }
+            _ => Ok(None)
+        }
+    }
+
+    fn parse_command(&self) -> Action {
+        match self.command.trim() {
+            "w" => Action::Write,
+            "q" => Action::Quit,
+            "wq" => Action::WriteAndQuit,
+            "nu" => Action::SetRelativeNumbers(false),
+            "rnu" => Action::SetRelativeNumbers(true),
+            cmd => match cmd.parse::<u16>() {
+                Ok(n) => Action::GotoLine(n),
+                Err(_) => Action::EnterMode(Mode::Normal),
+            },
+        }
+    }
 }
\ No newline at end of file
@@ -6,6 +6,8 @@ use logger::Logger;
 mod editor;
 mod buffer;
 mod logger;
+mod theme;
+mod config;
 
 static LOGGER: OnceLock<Logger> = OnceLock::new();
 
@@ -0,0 +1,40 @@
+use crossterm::style::Color;
+use syntect::parsing::ScopeStack;
+
+// Foreground used when no rule below matches any scope on the stack.
+const DEFAULT: Color = Color::Grey;
+
+// Walks the stack from the innermost scope outward and returns the color
+// for the first recognized TextMate scope prefix. A short, hand-picked
+// table rather than a full `syntect::highlighting::Theme`, so the editor
+// doesn't need to ship or parse `.tmTheme` files.
+pub fn color_for_scope(stack: &ScopeStack) -> Color {
+    for scope in stack.as_slice().iter().rev() {
+        let name = scope.build_string();
+        if name.starts_with("comment") {
+            return Color::DarkGrey;
+        }
+        if name.starts_with("string") {
+            return Color::Green;
+        }
+        if name.starts_with("constant.numeric") {
+            return Color::Magenta;
+        }
+        if name.starts_with("constant") {
+            return Color::DarkYellow;
+        }
+        if name.starts_with("keyword") {
+            return Color::Blue;
+        }
+        if name.starts_with("entity.name.function") {
+            return Color::Yellow;
+        }
+        if name.starts_with("entity.name.type") || name.starts_with("support.type") {
+            return Color::Cyan;
+        }
+        if name.starts_with("variable") {
+            return Color::White;
+        }
+    }
+    DEFAULT
+}